@@ -84,6 +84,36 @@ impl AstNode for Binary
     }
 }
 
+#[derive(Debug)]
+pub struct Bool
+{
+    pub value: bool,
+}
+
+impl AstNode for Bool
+{
+    fn print(&self, indent: usize)
+    {
+        println!("{}Bool {}", indent_str(indent), self.value);
+    }
+}
+
+#[derive(Debug)]
+pub struct Unary
+{
+    pub op: String,
+    pub operand: Box<Ast>,
+}
+
+impl AstNode for Unary
+{
+    fn print(&self, indent: usize)
+    {
+        println!("{}Unary '{}'", indent_str(indent), self.op);
+        self.operand.print(indent + 1);
+    }
+}
+
 #[derive(Debug)]
 pub struct Call
 {
@@ -143,17 +173,105 @@ impl AstNode for Return
     }
 }
 
+#[derive(Debug)]
+pub struct If
+{
+    pub cond: Box<Ast>,
+    pub then_body: Vec<Ast>,
+    pub elif_branches: Vec<(Ast, Vec<Ast>)>,
+    pub else_body: Option<Vec<Ast>>,
+}
+
+impl AstNode for If
+{
+    fn print(&self, indent: usize)
+    {
+        println!("{}If", indent_str(indent));
+        self.cond.print(indent + 1);
+        println!("{}Then:", indent_str(indent + 1));
+        for stmt in &self.then_body
+        {
+            stmt.print(indent + 2);
+        }
+        for (cond, body) in &self.elif_branches
+        {
+            println!("{}Elseif", indent_str(indent + 1));
+            cond.print(indent + 2);
+            for stmt in body
+            {
+                stmt.print(indent + 2);
+            }
+        }
+        if let Some(body) = &self.else_body
+        {
+            println!("{}Else:", indent_str(indent + 1));
+            for stmt in body
+            {
+                stmt.print(indent + 2);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct While
+{
+    pub cond: Box<Ast>,
+    pub body: Vec<Ast>,
+}
+
+impl AstNode for While
+{
+    fn print(&self, indent: usize)
+    {
+        println!("{}While", indent_str(indent));
+        self.cond.print(indent + 1);
+        println!("{}Body:", indent_str(indent + 1));
+        for stmt in &self.body
+        {
+            stmt.print(indent + 2);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct For
+{
+    pub var: String,
+    pub iterable: Box<Ast>,
+    pub body: Vec<Ast>,
+}
+
+impl AstNode for For
+{
+    fn print(&self, indent: usize)
+    {
+        println!("{}For {}", indent_str(indent), self.var);
+        self.iterable.print(indent + 1);
+        println!("{}Body:", indent_str(indent + 1));
+        for stmt in &self.body
+        {
+            stmt.print(indent + 2);
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Ast
 {
     Var(Var),
     Number(Number),
     Str(Str),
+    Bool(Bool),
     Identifier(Identifier),
     Binary(Binary),
+    Unary(Unary),
     Call(Call),
     Function(Function),
     Return(Return),
+    If(If),
+    While(While),
+    For(For),
 }
 
 impl AstNode for Ast
@@ -165,11 +283,16 @@ impl AstNode for Ast
             Ast::Var(v) => v.print(indent),
             Ast::Number(n) => n.print(indent),
             Ast::Str(s) => s.print(indent),
+            Ast::Bool(b) => b.print(indent),
             Ast::Identifier(i) => i.print(indent),
             Ast::Binary(b) => b.print(indent),
+            Ast::Unary(u) => u.print(indent),
             Ast::Call(c) => c.print(indent),
             Ast::Function(f) => f.print(indent),
             Ast::Return(r) => r.print(indent),
+            Ast::If(i) => i.print(indent),
+            Ast::While(w) => w.print(indent),
+            Ast::For(f) => f.print(indent),
         }
     }
 }
\ No newline at end of file
@@ -13,6 +13,18 @@ pub enum Opcode
     Sub,
     Mul,
     Div,
+    Eq,
+    Neq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    And,
+    Or,
+    Neg,
+    Not,
+    Jump,
+    JumpIfFalse,
     Halt,
     Call,
     Func,
@@ -40,6 +52,18 @@ fn opcode_char_map() -> HashMap<Opcode, u8>
         (Ret, 0x0E),
         (Readln, 0xA0),
         (Set, 0x0F),
+        (Eq, 0x10),
+        (Neq, 0x11),
+        (Lt, 0x12),
+        (Lte, 0x13),
+        (Gt, 0x14),
+        (Gte, 0x15),
+        (And, 0x16),
+        (Or, 0x17),
+        (Neg, 0x18),
+        (Not, 0x19),
+        (Jump, 0x1A),
+        (JumpIfFalse, 0x1B),
     ])
 }
 
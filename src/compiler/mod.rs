@@ -0,0 +1,274 @@
+use crate::ast::Ast;
+use crate::codegen::{Instruction, Opcode};
+
+/// Lowers a parsed program into the flat `Instruction` stream the `Vm`
+/// interprets. `if`/`while`/`for` lower to forward/backward jumps over a
+/// condition's `JumpIfFalse`, backpatched once the jump target is known.
+pub struct Compiler
+{
+    instructions: Vec<Instruction>,
+}
+
+impl Default for Compiler
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Compiler
+{
+    pub fn new() -> Self
+    {
+        Compiler { instructions: Vec::new() }
+    }
+
+    pub fn compile(&mut self, program: &[Ast]) -> Vec<Instruction>
+    {
+        for stmt in program
+        {
+            self.compile_stmt(stmt);
+        }
+        self.instructions.push(Instruction::Op(Opcode::Halt));
+
+        std::mem::take(&mut self.instructions)
+    }
+
+    /// Emits a jump with a placeholder target and returns its index so a
+    /// later call to `patch_jump` can fill in the real destination once it's
+    /// known.
+    fn emit_jump(&mut self, opcode: Opcode) -> usize
+    {
+        self.instructions.push(Instruction::OpWithArg(opcode, String::new()));
+        self.instructions.len() - 1
+    }
+
+    /// Points the jump emitted at `at` to the instruction stream's current
+    /// end, i.e. "right here".
+    fn patch_jump(&mut self, at: usize)
+    {
+        let target = self.instructions.len();
+        if let Instruction::OpWithArg(opcode, _) = &self.instructions[at]
+        {
+            self.instructions[at] = Instruction::OpWithArg(opcode.clone(), target.to_string());
+        }
+    }
+
+    fn compile_stmt(&mut self, ast: &Ast)
+    {
+        match ast
+        {
+            Ast::Var(v) =>
+            {
+                self.compile_expr(&v.value);
+                self.instructions.push(Instruction::OpWithArg(Opcode::Stv, v.name.clone()));
+            }
+            Ast::Function(f) =>
+            {
+                self.instructions.push(Instruction::OpWithArg(Opcode::Func, f.name.clone()));
+                // Records the declared parameter count so the Vm can balance
+                // the stack at the call site if a caller passes the wrong
+                // number of arguments, instead of leaving the mismatch to
+                // corrupt whatever runs next.
+                self.instructions.push(Instruction::Data(format!("arity:{}:{}", f.name, f.params.len())));
+                // Arguments arrive on the stack in call order, so bind them
+                // to parameter names back-to-front as they're popped. The Vm
+                // pushes a fresh scope for every call, so these bindings are
+                // local to this call and can't collide with the caller's.
+                for param in f.params.iter().rev()
+                {
+                    self.instructions.push(Instruction::OpWithArg(Opcode::Stv, param.clone()));
+                }
+                for stmt in &f.body
+                {
+                    self.compile_stmt(stmt);
+                }
+                self.instructions.push(Instruction::Op(Opcode::Ret));
+                // Marks where the function's body ends so the Vm can skip
+                // past it at the definition site instead of assuming the
+                // body runs to the next `Func` or end of the stream.
+                self.instructions.push(Instruction::Data(format!("endfunc:{}", f.name)));
+            }
+            Ast::Return(r) =>
+            {
+                if let Some(value) = &r.value
+                {
+                    self.compile_expr(value);
+                }
+                self.instructions.push(Instruction::Op(Opcode::Ret));
+            }
+            Ast::Call(c) if matches!(c.callee.as_str(), "print" | "println") =>
+            {
+                self.compile_expr(ast);
+            }
+            Ast::If(i) =>
+            {
+                let mut end_jumps = Vec::new();
+
+                self.compile_expr(&i.cond);
+                let mut next_jump = self.emit_jump(Opcode::JumpIfFalse);
+                for stmt in &i.then_body
+                {
+                    self.compile_stmt(stmt);
+                }
+                end_jumps.push(self.emit_jump(Opcode::Jump));
+                self.patch_jump(next_jump);
+
+                for (cond, body) in &i.elif_branches
+                {
+                    self.compile_expr(cond);
+                    next_jump = self.emit_jump(Opcode::JumpIfFalse);
+                    for stmt in body
+                    {
+                        self.compile_stmt(stmt);
+                    }
+                    end_jumps.push(self.emit_jump(Opcode::Jump));
+                    self.patch_jump(next_jump);
+                }
+
+                if let Some(body) = &i.else_body
+                {
+                    for stmt in body
+                    {
+                        self.compile_stmt(stmt);
+                    }
+                }
+
+                for jump in end_jumps
+                {
+                    self.patch_jump(jump);
+                }
+            }
+            Ast::While(w) =>
+            {
+                let loop_start = self.instructions.len();
+                self.compile_expr(&w.cond);
+                let exit_jump = self.emit_jump(Opcode::JumpIfFalse);
+                for stmt in &w.body
+                {
+                    self.compile_stmt(stmt);
+                }
+                self.instructions.push(Instruction::OpWithArg(Opcode::Jump, loop_start.to_string()));
+                self.patch_jump(exit_jump);
+            }
+            Ast::For(f) =>
+            {
+                // The language has no range or collection type, so `for x in
+                // n` counts `x` from 0 up to (but not including) `n`, mirroring
+                // how other toy languages treat a bare number as a bound.
+                self.compile_expr(&f.iterable);
+                let bound_var = format!("__for_bound_{}", self.instructions.len());
+                self.instructions.push(Instruction::OpWithArg(Opcode::Stv, bound_var.clone()));
+
+                self.instructions.push(Instruction::OpWithArg(Opcode::Push, "n:0".to_string()));
+                self.instructions.push(Instruction::OpWithArg(Opcode::Stv, f.var.clone()));
+
+                let loop_start = self.instructions.len();
+                self.instructions.push(Instruction::OpWithArg(Opcode::Ldv, f.var.clone()));
+                self.instructions.push(Instruction::OpWithArg(Opcode::Ldv, bound_var));
+                self.instructions.push(Instruction::Op(Opcode::Lt));
+                let exit_jump = self.emit_jump(Opcode::JumpIfFalse);
+
+                for stmt in &f.body
+                {
+                    self.compile_stmt(stmt);
+                }
+
+                self.instructions.push(Instruction::OpWithArg(Opcode::Ldv, f.var.clone()));
+                self.instructions.push(Instruction::OpWithArg(Opcode::Push, "n:1".to_string()));
+                self.instructions.push(Instruction::Op(Opcode::Add));
+                self.instructions.push(Instruction::OpWithArg(Opcode::Stv, f.var.clone()));
+
+                self.instructions.push(Instruction::OpWithArg(Opcode::Jump, loop_start.to_string()));
+                self.patch_jump(exit_jump);
+            }
+            _ =>
+            {
+                self.compile_expr(ast);
+                self.instructions.push(Instruction::Op(Opcode::Pop));
+            }
+        }
+    }
+
+    fn compile_expr(&mut self, ast: &Ast)
+    {
+        match ast
+        {
+            Ast::Number(n) =>
+            {
+                self.instructions.push(Instruction::OpWithArg(Opcode::Push, format!("n:{}", n.value)));
+            }
+            Ast::Str(s) =>
+            {
+                self.instructions.push(Instruction::OpWithArg(Opcode::Push, format!("s:{}", s.value)));
+            }
+            Ast::Bool(b) =>
+            {
+                self.instructions.push(Instruction::OpWithArg(Opcode::Push, format!("b:{}", b.value)));
+            }
+            Ast::Identifier(i) =>
+            {
+                self.instructions.push(Instruction::OpWithArg(Opcode::Ldv, i.name.clone()));
+            }
+            Ast::Binary(b) =>
+            {
+                self.compile_expr(&b.left);
+                self.compile_expr(&b.right);
+                if let Some(opcode) = Self::binary_opcode(&b.op)
+                {
+                    self.instructions.push(Instruction::Op(opcode));
+                }
+            }
+            Ast::Unary(u) =>
+            {
+                self.compile_expr(&u.operand);
+                match u.op.as_str()
+                {
+                    "-" => self.instructions.push(Instruction::Op(Opcode::Neg)),
+                    "!" => self.instructions.push(Instruction::Op(Opcode::Not)),
+                    _ => {}
+                }
+            }
+            Ast::Call(c) =>
+            {
+                for arg in &c.args
+                {
+                    self.compile_expr(arg);
+                }
+
+                match c.callee.as_str()
+                {
+                    "print" => self.instructions.push(Instruction::Op(Opcode::Print)),
+                    "println" => self.instructions.push(Instruction::Op(Opcode::Println)),
+                    "readln" => self.instructions.push(Instruction::Op(Opcode::Readln)),
+                    // The callee's arg count rides along with its name so the
+                    // Vm can compare it against the declared parameter count
+                    // and keep the stack balanced on a mismatch.
+                    _ => self.instructions.push(Instruction::OpWithArg(Opcode::Call, format!("{}:{}", c.callee, c.args.len()))),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn binary_opcode(op: &str) -> Option<Opcode>
+    {
+        match op
+        {
+            "+" => Some(Opcode::Add),
+            "-" => Some(Opcode::Sub),
+            "*" => Some(Opcode::Mul),
+            "/" => Some(Opcode::Div),
+            "==" => Some(Opcode::Eq),
+            "!=" => Some(Opcode::Neq),
+            "<" => Some(Opcode::Lt),
+            "<=" => Some(Opcode::Lte),
+            ">" => Some(Opcode::Gt),
+            ">=" => Some(Opcode::Gte),
+            "&&" => Some(Opcode::And),
+            "||" => Some(Opcode::Or),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span
+{
+    pub start_line: u32,
+    pub start_col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+}
+
+impl Span
+{
+    pub fn new(start_line: u32, start_col: u32, end_line: u32, end_col: u32) -> Self
+    {
+        Span { start_line, start_col, end_line, end_col }
+    }
+
+    pub fn point(line: u32, col: u32) -> Self
+    {
+        Span::new(line, col, line, col)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LexError
+{
+    UnexpectedChar(char),
+    UnterminatedString,
+    MalformedEscapeSequence(char),
+    MalformedNumber,
+}
+
+#[derive(Debug, Clone)]
+pub enum ParseError
+{
+    MissingDelimiter { expected: String, found: String },
+    VarExpectsIdentifier,
+    UnknownKeyword(String),
+    UnexpectedToken(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum DiagnosticKind
+{
+    Lex(LexError),
+    Parse(ParseError),
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic
+{
+    pub kind: DiagnosticKind,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Diagnostic
+{
+    pub fn lex(kind: LexError, span: Span, message: String) -> Self
+    {
+        Diagnostic { kind: DiagnosticKind::Lex(kind), span, message }
+    }
+
+    pub fn parse(kind: ParseError, span: Span, message: String) -> Self
+    {
+        Diagnostic { kind: DiagnosticKind::Parse(kind), span, message }
+    }
+}
+
+/// Prints diagnostics to stderr the way both the file runner and the REPL
+/// want them reported, without exiting the process.
+pub fn report(diagnostics: &[Diagnostic])
+{
+    for diagnostic in diagnostics
+    {
+        eprintln!(
+            "error: {} ({}:{})",
+            diagnostic.message, diagnostic.span.start_line, diagnostic.span.start_col
+        );
+    }
+}
@@ -1,17 +1,21 @@
 use strum_macros::{EnumString, AsRefStr};
-const OPERATORS: [&str; 10] = 
+use crate::diagnostics::{Diagnostic, LexError, Span};
+
+const OPERATORS: [&str; 18] =
 [
     "+" , "-" , "*" , "/" , "=",
-    "+=", "-=", "*=", "/=", "=="
+    "+=", "-=", "*=", "/=", "==",
+    "<", ">", "<=", ">=", "!=",
+    "&&", "||", "!"
 ];
 
-const DELIMITERS: [&str; 7] = 
+const DELIMITERS: [&str; 7] =
 [
     "(", ")", "{", "}", ".", ",",
     ";"
 ];
 
-const KEYWORDS: [&str; 13] = 
+const KEYWORDS: [&str; 13] =
 [
     "if", "else", "elseif", "var", "const",
     "fn", "return", "for", "in", "while", "once",
@@ -19,7 +23,7 @@ const KEYWORDS: [&str; 13] =
 ];
 
 #[derive(PartialEq, AsRefStr, EnumString, Clone)]
-pub enum TOT 
+pub enum TOT
 {
     #[strum(serialize = "identifier")]
     IDENTIFIER,
@@ -40,60 +44,64 @@ pub enum TOT
 }
 
 #[derive(Clone)]
-pub struct Token 
+pub struct Token
 {
     pub value: String,
-    pub tot: TOT
+    pub tot: TOT,
+    pub line: u32,
+    pub column: u32,
 }
 
-impl Token 
+impl Token
 {
-    pub fn new(value: String, tot: TOT) -> Self 
+    pub fn new(value: String, tot: TOT, line: u32, column: u32) -> Self
     {
-        Token { value, tot }
+        Token { value, tot, line, column }
     }
 }
 
-pub struct Lexer 
+pub struct Lexer
 {
     src: String,
     index: usize,
     line: u32,
-    column: u32
+    column: u32,
+    diagnostics: Vec<Diagnostic>,
 }
 
-impl Lexer 
+impl Lexer
 {
     pub fn new(src: String) -> Self
     {
-        Lexer 
+        Lexer
         {
             src: src,
             index: 0,
             line: 1,
             column: 1,
+            diagnostics: Vec::new(),
         }
     }
 
-    fn is_eof(&self) -> bool 
+    fn is_eof(&self) -> bool
     {
         self.index >= self.src.chars().count()
     }
 
-    fn peek(&self, offset: usize) -> char 
+    fn peek(&self, offset: usize) -> char
     {
         self.src.chars().nth(self.index + offset).unwrap_or('\0')
     }
 
-    fn next_char(&mut self) -> char 
+    fn next_char(&mut self) -> char
     {
         let c = self.src.chars().nth(self.index).unwrap_or('\0');
-        if c == '\n' 
+        if c == '\n'
         {
             self.line += 1;
             self.column = 1;
         }
-        else 
+        else
         {
             self.column += 1;
         }
@@ -101,123 +109,315 @@ impl Lexer
         c
     }
 
-    fn expect(&mut self, expected: char) 
+    fn push_error(&mut self, err: LexError, line: u32, column: u32, message: String)
+    {
+        self.diagnostics.push(Diagnostic::lex(err, Span::point(line, column), message));
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Vec<Diagnostic>>
     {
-        let actual = self.peek(0);
-        if actual != expected 
+        let mut tokens = Vec::new();
+        while !self.is_eof()
         {
-            panic!("{}", format!(
-                "Expected '{}' but found '{}' at line '{}', column '{}'.", 
-                expected, actual, self.line, self.column
-            ))
+            tokens.extend(self.read_next());
+        }
+
+        if self.diagnostics.is_empty()
+        {
+            Ok(tokens)
+        }
+        else
+        {
+            Err(std::mem::take(&mut self.diagnostics))
         }
-        self.next_char();
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token>
+    /// Reads whatever comes next at the cursor: zero tokens for skipped
+    /// whitespace, one token for most lexemes, or several for a string
+    /// literal that contains `{ ... }` interpolation. Shared by the top-level
+    /// loop and by interpolated-expression scanning so both go through the
+    /// same dispatch.
+    fn read_next(&mut self) -> Vec<Token>
     {
-        let mut tokens = Vec::new();
-        while !self.is_eof() 
+        let c = self.peek(0);
+
+        if c.is_whitespace()
         {
-            let c = self.peek(0);
-            if c.is_whitespace() 
+            if c == '\n'
             {
-                if c == '\n' 
-                {
-                    self.next_char();
-                    tokens.push(Token::new("[newline]".to_string(), TOT::NEWLINE));
-                }
-                else 
-                {
-                    self.next_char();    
-                }
-            }
-            else if c.is_numeric() 
-            {
-                tokens.push(self.read_number())
-            }
-            else if c.is_alphanumeric() 
-            {
-                tokens.push(self.read_identifier_or_keyword())
+                let (line, column) = (self.line, self.column);
+                self.next_char();
+                vec![Token::new("[newline]".to_string(), TOT::NEWLINE, line, column)]
             }
-            else if DELIMITERS.contains(&c.to_string().as_str())
+            else
             {
                 self.next_char();
-                tokens.push(Token::new(c.to_string(), TOT::DELIMITER))
+                Vec::new()
             }
-            else if c == '"'
+        }
+        else if c.is_numeric()
+        {
+            vec![self.read_number()]
+        }
+        else if c.is_alphanumeric()
+        {
+            vec![self.read_identifier_or_keyword()]
+        }
+        else if DELIMITERS.contains(&c.to_string().as_str())
+        {
+            let (line, column) = (self.line, self.column);
+            self.next_char();
+            vec![Token::new(c.to_string(), TOT::DELIMITER, line, column)]
+        }
+        else if c == '"'
+        {
+            self.read_string()
+        }
+        else if c == '/'
+        {
+            vec![self.read_comment()]
+        }
+        else if OPERATORS.iter().any(|op| op.starts_with(c))
+        {
+            vec![self.read_operator()]
+        }
+        else
+        {
+            let (line, column) = (self.line, self.column);
+            self.push_error(
+                LexError::UnexpectedChar(c),
+                line,
+                column,
+                format!("Unknown character '{}' at line {}, column {}.", c, line, column),
+            );
+            self.next_char();
+            Vec::new()
+        }
+    }
+
+    /// Reads a number literal, accepting a single `.` fraction, `_` digit
+    /// separators (stripped before the parser ever sees the value), and an
+    /// optional `e`/`E` exponent. A doubled decimal point or a trailing
+    /// exponent with no digits is reported as `MalformedNumber` rather than
+    /// left for `str::parse` to panic on later. Negative literals are not
+    /// handled here — that's the unary-minus parser's job, to keep `3 - 1`
+    /// unambiguous.
+    fn read_number(&mut self) -> Token
+    {
+        let (line, column) = (self.line, self.column);
+        let start = self.index;
+        let mut malformed = false;
+
+        self.consume_digits();
+
+        if self.peek(0) == '.' && self.peek(1).is_numeric()
+        {
+            self.next_char();
+            self.consume_digits();
+        }
+
+        if self.peek(0) == '.'
+        {
+            malformed = true;
+            while self.peek(0) == '.' || self.peek(0).is_numeric() || self.peek(0) == '_'
             {
-                tokens.push(self.read_string())
+                self.next_char();
             }
-            else if c == '/'
+        }
+
+        if self.peek(0) == 'e' || self.peek(0) == 'E'
+        {
+            self.next_char();
+            if self.peek(0) == '+' || self.peek(0) == '-'
             {
-                tokens.push(self.read_comment())
+                self.next_char();
             }
-            else if OPERATORS.contains(&c.to_string().as_str())
+
+            if self.peek(0).is_numeric()
             {
-                tokens.push(self.read_operator());
+                self.consume_digits();
             }
             else
             {
-                panic!("{}", format!(
-                    "Unknown character '{}' at line {}, column {}.",
-                    c, self.line, self.column
-                ))
+                malformed = true;
             }
         }
-        tokens
+
+        let raw = &self.src[start..self.index];
+        let cleaned: String = raw.chars().filter(|c| *c != '_').collect();
+
+        if malformed
+        {
+            self.push_error(
+                LexError::MalformedNumber,
+                line,
+                column,
+                format!("Malformed number literal '{}'", raw),
+            );
+        }
+
+        Token::new(cleaned, TOT::NUMBER, line, column)
     }
 
-    fn read_number(&mut self) -> Token 
+    fn consume_digits(&mut self)
     {
-        let start = self.index;
-        while self.peek(0).is_numeric() 
+        while self.peek(0).is_numeric() || self.peek(0) == '_'
         {
             self.next_char();
         }
-        let value = &self.src[start..self.index];
-        Token::new(value.to_string(), TOT::NUMBER)
     }
 
-    fn read_identifier_or_keyword(&mut self) -> Token 
+    fn read_identifier_or_keyword(&mut self) -> Token
     {
+        let (line, column) = (self.line, self.column);
         let start = self.index;
-        while self.peek(0).is_alphanumeric() 
+        while self.peek(0).is_alphanumeric()
         {
             self.next_char();
         }
         let value = &self.src[start..self.index];
-        if KEYWORDS.contains(&value) 
+        if KEYWORDS.contains(&value)
         {
-            Token::new(value.to_string(), TOT::KEYWORD)
+            Token::new(value.to_string(), TOT::KEYWORD, line, column)
         }
         else
         {
-            Token::new(value.to_string(), TOT::IDENTIFIER)
+            Token::new(value.to_string(), TOT::IDENTIFIER, line, column)
         }
     }
 
-    fn read_string(&mut self) -> Token 
+    /// Reads a string literal, translating `\n`/`\t`/`\r`/`\\`/`\"`/`\0`
+    /// escapes and splitting on `{ ... }` interpolation. An interpolated
+    /// string like `"x = {expr}"` comes out as the token sequence
+    /// `STRING("x = ") OPERATOR(+) DELIMITER(() <tokens of expr> DELIMITER())
+    /// OPERATOR(+) STRING("")`. The synthetic parens make `expr` parse as a
+    /// single grouped sub-expression before the `+`-folding takes over, so an
+    /// interpolated expression with its own `+`/`==`/etc. (e.g. `"{a + b}"`)
+    /// evaluates on its own rather than being spliced into the surrounding
+    /// concatenation at `+`-precedence — no new AST or opcode needed for
+    /// interpolation itself.
+    fn read_string(&mut self) -> Vec<Token>
     {
+        let (line, column) = (self.line, self.column);
         self.next_char();
-        let mut str = String::new();
-        while !self.is_eof() && self.peek(0) != '"' 
+
+        let mut tokens = Vec::new();
+        let mut chunk = String::new();
+        let (mut chunk_line, mut chunk_col) = (self.line, self.column);
+
+        loop
         {
-            str += self.next_char().to_string().as_str();
+            if self.is_eof()
+            {
+                self.push_error(
+                    LexError::UnterminatedString,
+                    line,
+                    column,
+                    format!("Unterminated string starting at line {}, column {}", line, column),
+                );
+                break;
+            }
+
+            match self.peek(0)
+            {
+                '"' =>
+                {
+                    self.next_char();
+                    break;
+                }
+                '\\' =>
+                {
+                    self.next_char();
+                    let (escape_line, escape_col) = (self.line, self.column);
+                    let escaped = self.next_char();
+                    match escaped
+                    {
+                        'n' => chunk.push('\n'),
+                        't' => chunk.push('\t'),
+                        'r' => chunk.push('\r'),
+                        '\\' => chunk.push('\\'),
+                        '"' => chunk.push('"'),
+                        '0' => chunk.push('\0'),
+                        other =>
+                        {
+                            self.push_error(
+                                LexError::MalformedEscapeSequence(other),
+                                escape_line,
+                                escape_col,
+                                format!("Unknown escape sequence '\\{}'", other),
+                            );
+                            chunk.push(other);
+                        }
+                    }
+                }
+                '{' =>
+                {
+                    self.next_char();
+                    tokens.push(Token::new(chunk.clone(), TOT::STRING, chunk_line, chunk_col));
+                    chunk.clear();
+
+                    let (op_line, op_col) = (self.line, self.column);
+                    tokens.push(Token::new("+".to_string(), TOT::OPERATOR, op_line, op_col));
+                    tokens.push(Token::new("(".to_string(), TOT::DELIMITER, op_line, op_col));
+                    tokens.extend(self.read_interpolation());
+                    tokens.push(Token::new(")".to_string(), TOT::DELIMITER, op_line, op_col));
+                    tokens.push(Token::new("+".to_string(), TOT::OPERATOR, op_line, op_col));
+
+                    chunk_line = self.line;
+                    chunk_col = self.column;
+                }
+                _ =>
+                {
+                    chunk.push(self.next_char());
+                }
+            }
         }
-        if self.is_eof() 
+
+        tokens.push(Token::new(chunk, TOT::STRING, chunk_line, chunk_col));
+        tokens
+    }
+
+    /// Lexes the expression inside a string's `{ ... }` the same way the
+    /// top-level loop lexes everything else, stopping at the matching `}`
+    /// (tracking nested `{`/`}` delimiter tokens so a brace pair inside the
+    /// expression doesn't end it early; a nested interpolated string consumes
+    /// its own braces in `read_string` and never reaches this counter).
+    fn read_interpolation(&mut self) -> Vec<Token>
+    {
+        let mut tokens = Vec::new();
+        let mut depth = 1;
+
+        while !self.is_eof()
         {
-            panic!("{}", format!(
-                "Unterminated string at line {}, column {}",
-                self.line, self.column
-            ));
+            for token in self.read_next()
+            {
+                if token.tot == TOT::DELIMITER && token.value == "{"
+                {
+                    depth += 1;
+                }
+                else if token.tot == TOT::DELIMITER && token.value == "}"
+                {
+                    depth -= 1;
+                    if depth == 0
+                    {
+                        return tokens;
+                    }
+                }
+                tokens.push(token);
+            }
+
+            if depth == 0
+            {
+                break;
+            }
         }
-        self.expect('"');
-        Token::new(str, TOT::STRING)
+
+        tokens
     }
 
-    fn read_operator(&mut self) -> Token 
+    fn read_operator(&mut self) -> Token
     {
+        let (line, column) = (self.line, self.column);
         let first = self.peek(0);
         let second = self.peek(1);
         let combined = first.to_string() + second.to_string().as_str();
@@ -226,52 +426,57 @@ impl Lexer
         {
             self.next_char();
             self.next_char();
-            Token::new(combined, TOT::OPERATOR)
+            Token::new(combined, TOT::OPERATOR, line, column)
         }
         else if OPERATORS.contains(&first.to_string().as_str())
         {
             self.next_char();
-            Token::new(first.to_string(), TOT::OPERATOR)
+            Token::new(first.to_string(), TOT::OPERATOR, line, column)
         }
-        else 
+        else
         {
-            panic!("{}", format!(
-                "Unknown character '{}' at line {}, column {}.",
-                first, self.line, self.column
-            ))
+            self.push_error(
+                LexError::UnexpectedChar(first),
+                line,
+                column,
+                format!("Unknown character '{}' at line {}, column {}.", first, line, column),
+            );
+            self.next_char();
+            Token::new(first.to_string(), TOT::OPERATOR, line, column)
         }
     }
 
     fn read_comment(&mut self) -> Token
     {
+        let (line, column) = (self.line, self.column);
         let next = self.peek(1);
 
-        if next == '/' 
+        if next == '/'
         {
             self.next_char();
             self.next_char();
             let mut comment = String::new();
-            while !self.is_eof() && self.peek(0) != '\n' 
+            while !self.is_eof() && self.peek(0) != '\n'
             {
                 comment += self.next_char().to_string().as_str();
             }
-            Token::new(comment, TOT::COMMENT)
+            Token::new(comment, TOT::COMMENT, line, column)
         }
-        else if next == '*' 
+        else if next == '*'
         {
             self.next_char();
             self.next_char();
             let mut comment = String::new();
-            while !self.is_eof() && !(self.peek(0) == '*' && self.peek(1) == '/') 
+            while !self.is_eof() && !(self.peek(0) == '*' && self.peek(1) == '/')
             {
                 comment += self.next_char().to_string().as_str();
             }
-            Token::new(comment, TOT::COMMENT)
+            Token::new(comment, TOT::COMMENT, line, column)
         }
-        else 
+        else
         {
             self.next_char();
-            Token::new("/".to_string(), TOT::OPERATOR)    
+            Token::new("/".to_string(), TOT::OPERATOR, line, column)
         }
     }
-}
\ No newline at end of file
+}
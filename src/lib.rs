@@ -0,0 +1,8 @@
+pub mod ast;
+pub mod codegen;
+pub mod compiler;
+pub mod diagnostics;
+pub mod lexer;
+pub mod parser;
+pub mod repl;
+pub mod vm;
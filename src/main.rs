@@ -1,30 +1,71 @@
 use nano::{
+    ast::AstNode,
+    compiler::Compiler,
+    diagnostics::report,
     lexer::Lexer,
     parser::Parser,
-    ast::AstNode,
+    repl::Repl,
+    vm::Vm,
 };
 use std::fs;
 use std::io::Read;
 use std::env;
 
-fn main() 
+fn main()
 {
     let args: Vec<String> = env::args().collect();
-    let filename = &args[1];
+    let repl_flag = args.iter().any(|arg| arg == "--repl");
+    let filename = args.iter().skip(1).find(|arg| !arg.starts_with("--"));
+
+    if repl_flag || filename.is_none()
+    {
+        Repl::new().run();
+        return;
+    }
+
+    let filename = filename.unwrap();
+    let run = args.iter().any(|arg| arg == "--run");
+
     let mut file = fs::File::open(filename).expect("Unable to open file");
     let mut contents = String::new();
     file.read_to_string(&mut contents).expect("Unable to read file");
+
     let mut lexer = Lexer::new(contents);
-    let tokens = lexer.tokenize();
-    for token in &tokens 
+    let tokens = match lexer.tokenize()
+    {
+        Ok(tokens) => tokens,
+        Err(diagnostics) =>
+        {
+            report(&diagnostics);
+            std::process::exit(1);
+        }
+    };
+
+    let mut parser = Parser::new(tokens.clone());
+    let ast = match parser.parse()
+    {
+        Ok(ast) => ast,
+        Err(diagnostics) =>
+        {
+            report(&diagnostics);
+            std::process::exit(1);
+        }
+    };
+
+    if run
+    {
+        let instructions = Compiler::new().compile(&ast);
+        Vm::new().execute(instructions);
+        return;
+    }
+
+    for token in &tokens
     {
         println!("{}", format!("{} : {}", token.value, token.tot.as_ref()))
     }
-    let mut parser = Parser::new(tokens.clone());
-    let ast = parser.parse();
-    for node in &ast 
+
+    for node in &ast
     {
         node.print(0);
     }
-    drop(ast);
-}   
+}
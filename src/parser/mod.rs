@@ -1,12 +1,17 @@
 use crate::lexer::{TOT, Token};
-use crate::ast::*; 
+use crate::ast::*;
+use crate::diagnostics::{Diagnostic, ParseError, Span};
+
+/// A parse step either produces a value or has already recorded a diagnostic
+/// for the failure; `Err(())` just means "give up on this node and let the
+/// caller decide how to recover".
+type PResult<T> = Result<T, ()>;
 
 pub struct Parser
 {
     tokens: Vec<Token>,
     index: usize,
-    line: usize,
-    column: usize,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Parser
@@ -17,8 +22,7 @@ impl Parser
         {
             tokens,
             index: 0,
-            line: 1,
-            column: 1,
+            diagnostics: Vec::new(),
         }
     }
 
@@ -32,22 +36,26 @@ impl Parser
         self.tokens.get(self.index + offset)
     }
 
-    fn next_token(&mut self) -> Option<Token>
+    fn current_span(&self) -> Span
     {
-        let token = self.peek(0)?.clone();
-
-        self.index += 1;
-
-        if token.tot == TOT::NEWLINE
+        if let Some(token) = self.peek(0)
+        {
+            Span::point(token.line, token.column)
+        }
+        else if let Some(token) = self.tokens.last()
         {
-            self.line += 1;
-            self.column = 1;
+            Span::point(token.line, token.column)
         }
         else
         {
-            self.column += token.value.len();
+            Span::point(1, 1)
         }
+    }
 
+    fn next_token(&mut self) -> Option<Token>
+    {
+        let token = self.peek(0)?.clone();
+        self.index += 1;
         Some(token)
     }
 
@@ -66,25 +74,27 @@ impl Parser
         }
     }
 
-    fn expect(&mut self, tot: TOT, value: Option<&str>) -> Token
+    fn expect(&mut self, tot: TOT, value: Option<&str>) -> PResult<Token>
     {
         if let Some(token) = self.match_token(tot.clone(), value)
         {
-            token
+            Ok(token)
         }
         else
         {
-            let found = self.peek(0).cloned().unwrap();
-
-            panic!(
-                "Expected {}{} at line {}, column {}, but found {}{}",
-                tot.as_ref(),
-                value.map_or(String::new(), |v| format!(" '{}'", v)),
-                self.line,
-                self.column,
-                found.tot.as_ref(),
-                found.value
-            )
+            let found = self.peek(0).cloned();
+            let expected = format!("{}{}", tot.as_ref(), value.map_or(String::new(), |v| format!(" '{}'", v)));
+            let found_desc = found
+                .as_ref()
+                .map_or("end of file".to_string(), |t| format!("{} '{}'", t.tot.as_ref(), t.value));
+
+            self.diagnostics.push(Diagnostic::parse(
+                ParseError::MissingDelimiter { expected: expected.clone(), found: found_desc.clone() },
+                self.current_span(),
+                format!("Expected {}, but found {}", expected, found_desc),
+            ));
+
+            Err(())
         }
     }
 
@@ -93,7 +103,40 @@ impl Parser
         while self.match_token(TOT::NEWLINE, None).is_some() {}
     }
 
-    pub fn parse(&mut self) -> Vec<Ast>
+    fn consume_comments(&mut self)
+    {
+        while matches!(self.peek(0), Some(Token { tot: TOT::COMMENT, .. }))
+        {
+            self.next_token();
+        }
+    }
+
+    /// Skips tokens until the next statement boundary (a `NEWLINE` or a
+    /// keyword that can start a new top-level statement), so one malformed
+    /// statement doesn't prevent the rest of the file from being checked.
+    fn synchronize(&mut self)
+    {
+        while !self.is_at_end()
+        {
+            if let Some(token) = self.peek(0)
+            {
+                if token.tot == TOT::NEWLINE
+                {
+                    self.next_token();
+                    return;
+                }
+
+                if token.tot == TOT::KEYWORD && matches!(token.value.as_str(), "var" | "fn" | "return" | "if" | "while" | "for")
+                {
+                    return;
+                }
+            }
+
+            self.next_token();
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Vec<Ast>, Vec<Diagnostic>>
     {
         let mut statements = Vec::new();
 
@@ -101,20 +144,37 @@ impl Parser
         {
             self.consume_newlines();
 
-            if !self.is_at_end()
+            if self.is_at_end()
+            {
+                break;
+            }
+
+            match self.parse_statement()
             {
-                statements.push(self.parse_statement());
+                Ok(stmt) => statements.push(stmt),
+                Err(()) => self.synchronize(),
             }
         }
 
-        statements
+        if self.diagnostics.is_empty()
+        {
+            Ok(statements)
+        }
+        else
+        {
+            Err(std::mem::take(&mut self.diagnostics))
+        }
     }
 
-    fn parse_statement(&mut self) -> Ast
+    fn parse_statement(&mut self) -> PResult<Ast>
     {
         self.consume_newlines();
 
-        let token = self.peek(0).cloned().unwrap();
+        let token = match self.peek(0).cloned()
+        {
+            Some(token) => token,
+            None => return Err(()),
+        };
 
         if token.tot == TOT::KEYWORD
         {
@@ -135,8 +195,31 @@ impl Parser
                     self.next_token();
                     self.parse_return()
                 }
+                "if" =>
+                {
+                    self.next_token();
+                    self.parse_if()
+                }
+                "while" =>
+                {
+                    self.next_token();
+                    self.parse_while()
+                }
+                "for" =>
+                {
+                    self.next_token();
+                    self.parse_for()
+                }
                 _ =>
-                    panic!("Unknown keyword: {:?}", token.value),
+                {
+                    self.diagnostics.push(Diagnostic::parse(
+                        ParseError::UnknownKeyword(token.value.clone()),
+                        Span::point(token.line, token.column),
+                        format!("Unknown keyword '{}'", token.value),
+                    ));
+                    self.next_token();
+                    Err(())
+                }
             }
         }
         else
@@ -145,23 +228,69 @@ impl Parser
         }
     }
 
-    fn parse_var_declaration(&mut self) -> Ast
+    fn parse_var_declaration(&mut self) -> PResult<Ast>
     {
-        let name = self.expect(TOT::IDENTIFIER, None).value;
-        self.expect(TOT::OPERATOR, Some("="));
-        let expr = self.parse_expression();
+        let name = match self.match_token(TOT::IDENTIFIER, None)
+        {
+            Some(token) => token.value,
+            None =>
+            {
+                self.diagnostics.push(Diagnostic::parse(
+                    ParseError::VarExpectsIdentifier,
+                    self.current_span(),
+                    "Expected an identifier after 'var'".to_string(),
+                ));
+                return Err(());
+            }
+        };
+
+        self.expect(TOT::OPERATOR, Some("="))?;
+        let expr = self.parse_expression()?;
 
-        Ast::Var(Var
+        Ok(Ast::Var(Var
         {
             name,
             value: Box::new(expr),
-        })
+        }))
+    }
+
+    /// Parses a `{ ... }` block, recovering statement-by-statement so one
+    /// bad line inside a body doesn't take the whole block down with it.
+    fn parse_block(&mut self) -> PResult<Vec<Ast>>
+    {
+        self.consume_newlines();
+        self.expect(TOT::DELIMITER, Some("{"))?;
+
+        let mut body = Vec::new();
+        self.consume_newlines();
+
+        while !self.is_at_end() && self.match_token(TOT::DELIMITER, Some("}")).is_none()
+        {
+            match self.parse_statement()
+            {
+                Ok(stmt) => body.push(stmt),
+                Err(()) => self.synchronize(),
+            }
+            self.consume_newlines();
+        }
+
+        if self.is_at_end()
+        {
+            self.diagnostics.push(Diagnostic::parse(
+                ParseError::MissingDelimiter { expected: "delimiter '}'".to_string(), found: "end of file".to_string() },
+                self.current_span(),
+                "Expected '}' to close block, but reached end of file".to_string(),
+            ));
+            return Err(());
+        }
+
+        Ok(body)
     }
 
-    fn parse_function(&mut self) -> Ast
+    fn parse_function(&mut self) -> PResult<Ast>
     {
-        let name = self.expect(TOT::IDENTIFIER, None).value;
-        self.expect(TOT::DELIMITER, Some("("));
+        let name = self.expect(TOT::IDENTIFIER, None)?.value;
+        self.expect(TOT::DELIMITER, Some("("))?;
 
         let mut params = Vec::new();
 
@@ -169,7 +298,7 @@ impl Parser
         {
             loop
             {
-                params.push(self.expect(TOT::IDENTIFIER, None).value);
+                params.push(self.expect(TOT::IDENTIFIER, None)?.value);
 
                 if self.match_token(TOT::DELIMITER, Some(",")).is_none()
                 {
@@ -177,133 +306,294 @@ impl Parser
                 }
             }
 
-            self.expect(TOT::DELIMITER, Some(")"));
+            self.expect(TOT::DELIMITER, Some(")"))?;
         }
 
+        let body = self.parse_block()?;
+
+        Ok(Ast::Function(Function
+        {
+            name,
+            params,
+            body,
+        }))
+    }
+
+    fn parse_return(&mut self) -> PResult<Ast>
+    {
         self.consume_newlines();
-        self.expect(TOT::DELIMITER, Some("{"));
+        let expr = self.parse_expression()?;
 
-        let mut body = Vec::new();
-        while !self.is_at_end() && self.match_token(TOT::DELIMITER, Some("}")).is_none()
+        Ok(Ast::Return(Return
+        {
+            value: Some(Box::new(expr)),
+        }))
+    }
+
+    fn parse_if(&mut self) -> PResult<Ast>
+    {
+        let cond = self.parse_expression()?;
+        let then_body = self.parse_block()?;
+
+        let mut elif_branches = Vec::new();
+        let mut else_body = None;
+
+        loop
         {
             self.consume_newlines();
 
-            if self.is_at_end()
+            if self.match_token(TOT::KEYWORD, Some("elseif")).is_some()
             {
-                panic!("Unexpected end of file inside function body");
+                let elif_cond = self.parse_expression()?;
+                let elif_body = self.parse_block()?;
+                elif_branches.push((elif_cond, elif_body));
             }
-
-            if let Some(token) = self.peek(0) 
+            else if self.match_token(TOT::KEYWORD, Some("else")).is_some()
             {
-                if token.tot == TOT::DELIMITER && token.value == "}" 
-                {
-                    break;
-                }
+                else_body = Some(self.parse_block()?);
+                break;
+            }
+            else
+            {
+                break;
             }
-            body.push(self.parse_statement());
         }
 
-        if self.is_at_end()
+        Ok(Ast::If(If
         {
-            panic!("Expected '}}' to close function body, but reached end of file");
-        }
+            cond: Box::new(cond),
+            then_body,
+            elif_branches,
+            else_body,
+        }))
+    }
 
-        self.expect(TOT::DELIMITER, Some("}"));
+    fn parse_while(&mut self) -> PResult<Ast>
+    {
+        let cond = self.parse_expression()?;
+        let body = self.parse_block()?;
 
-        Ast::Function(Function
+        Ok(Ast::While(While
         {
-            name,
-            params,
+            cond: Box::new(cond),
             body,
-        })
+        }))
     }
 
-    fn parse_return(&mut self) -> Ast
+    fn parse_for(&mut self) -> PResult<Ast>
     {
-        self.consume_newlines();
-        let expr = self.parse_expression();
+        let var = self.expect(TOT::IDENTIFIER, None)?.value;
+        self.expect(TOT::KEYWORD, Some("in"))?;
+        let iterable = self.parse_expression()?;
+        let body = self.parse_block()?;
 
-        Ast::Return(Return
+        Ok(Ast::For(For
         {
-            value: Some(Box::new(expr)),
-        })
+            var,
+            iterable: Box::new(iterable),
+            body,
+        }))
     }
 
-    fn parse_expression(&mut self) -> Ast 
+    fn parse_expression(&mut self) -> PResult<Ast>
     {
-        self.parse_addition()
+        self.parse_or()
     }
-    
-    fn parse_addition(&mut self) -> Ast 
+
+    fn parse_or(&mut self) -> PResult<Ast>
     {
-        let mut left = self.parse_multiplication();
+        let mut left = self.parse_and()?;
         while let Some(token) = self.peek(0)
         {
-            if token.tot == TOT::OPERATOR && (token.value == "+" || token.value == "-") 
+            if token.tot == TOT::OPERATOR && token.value == "||"
             {
                 let op = token.value.clone();
                 self.next_token();
-                let right = self.parse_multiplication();
-                left = Ast::Binary(Binary 
+                let right = self.parse_and()?;
+                left = Ast::Binary(Binary
                 {
                     op,
                     left: Box::new(left),
                     right: Box::new(right),
                 });
-            } 
-            else 
+            }
+            else
             {
                 break;
             }
         }
-        left
+        Ok(left)
     }
-    
-    fn parse_multiplication(&mut self) -> Ast 
+
+    fn parse_and(&mut self) -> PResult<Ast>
     {
-        let mut left = self.parse_primary();
-        while let Some(token) = self.peek(0) 
+        let mut left = self.parse_equality()?;
+        while let Some(token) = self.peek(0)
         {
-            if token.tot == TOT::OPERATOR && (token.value == "*" || token.value == "/") 
+            if token.tot == TOT::OPERATOR && token.value == "&&"
             {
                 let op = token.value.clone();
                 self.next_token();
-                let right = self.parse_primary();
-                left = Ast::Binary(Binary {
+                let right = self.parse_equality()?;
+                left = Ast::Binary(Binary
+                {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+            }
+            else
+            {
+                break;
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_equality(&mut self) -> PResult<Ast>
+    {
+        let mut left = self.parse_comparison()?;
+        while let Some(token) = self.peek(0)
+        {
+            if token.tot == TOT::OPERATOR && (token.value == "==" || token.value == "!=")
+            {
+                let op = token.value.clone();
+                self.next_token();
+                let right = self.parse_comparison()?;
+                left = Ast::Binary(Binary
+                {
                     op,
                     left: Box::new(left),
                     right: Box::new(right),
                 });
-            } 
-            else 
+            }
+            else
             {
                 break;
             }
         }
-        left
-    }    
+        Ok(left)
+    }
 
-    fn parse_grouping(&mut self) -> Ast
+    fn parse_comparison(&mut self) -> PResult<Ast>
     {
-        self.expect(TOT::DELIMITER, Some("("));
-        let expr = self.parse_expression();
-        self.expect(TOT::DELIMITER, Some(")"));
-        expr
+        let mut left = self.parse_addition()?;
+        while let Some(token) = self.peek(0)
+        {
+            if token.tot == TOT::OPERATOR && matches!(token.value.as_str(), "<" | "<=" | ">" | ">=")
+            {
+                let op = token.value.clone();
+                self.next_token();
+                let right = self.parse_addition()?;
+                left = Ast::Binary(Binary
+                {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+            }
+            else
+            {
+                break;
+            }
+        }
+        Ok(left)
     }
 
-    fn consume_comments(&mut self)
+    fn parse_addition(&mut self) -> PResult<Ast>
     {
-        while matches!(self.peek(0), Some(Token { tot: TOT::COMMENT, .. })) 
+        let mut left = self.parse_multiplication()?;
+        while let Some(token) = self.peek(0)
         {
-            self.next_token();
+            if token.tot == TOT::OPERATOR && (token.value == "+" || token.value == "-")
+            {
+                let op = token.value.clone();
+                self.next_token();
+                let right = self.parse_multiplication()?;
+                left = Ast::Binary(Binary
+                {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+            }
+            else
+            {
+                break;
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplication(&mut self) -> PResult<Ast>
+    {
+        let mut left = self.parse_unary()?;
+        while let Some(token) = self.peek(0)
+        {
+            if token.tot == TOT::OPERATOR && (token.value == "*" || token.value == "/")
+            {
+                let op = token.value.clone();
+                self.next_token();
+                let right = self.parse_unary()?;
+                left = Ast::Binary(Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+            }
+            else
+            {
+                break;
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> PResult<Ast>
+    {
+        if let Some(token) = self.peek(0)
+        {
+            if token.tot == TOT::OPERATOR && (token.value == "-" || token.value == "!")
+            {
+                let op = token.value.clone();
+                self.next_token();
+                let operand = self.parse_unary()?;
+                return Ok(Ast::Unary(Unary
+                {
+                    op,
+                    operand: Box::new(operand),
+                }));
+            }
         }
+
+        self.parse_primary()
     }
 
-    fn parse_primary(&mut self) -> Ast
+    fn parse_grouping(&mut self) -> PResult<Ast>
+    {
+        self.expect(TOT::DELIMITER, Some("("))?;
+        let expr = self.parse_expression()?;
+        self.expect(TOT::DELIMITER, Some(")"))?;
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> PResult<Ast>
     {
         self.consume_comments();
 
-        let token = self.peek(0).cloned().unwrap();
+        let token = match self.peek(0).cloned()
+        {
+            Some(token) => token,
+            None =>
+            {
+                self.diagnostics.push(Diagnostic::parse(
+                    ParseError::UnexpectedToken("end of file".to_string()),
+                    self.current_span(),
+                    "Unexpected end of file while parsing an expression".to_string(),
+                ));
+                return Err(());
+            }
+        };
 
         match token.tot
         {
@@ -315,18 +605,18 @@ impl Parser
             TOT::NUMBER =>
             {
                 self.next_token();
-                Ast::Number(Number
+                Ok(Ast::Number(Number
                 {
                     value: token.value.parse().unwrap(),
-                })
+                }))
             }
             TOT::STRING =>
             {
                 self.next_token();
-                Ast::Str(Str
+                Ok(Ast::Str(Str
                 {
                     value: token.value,
-                })
+                }))
             }
             TOT::IDENTIFIER =>
             {
@@ -339,24 +629,40 @@ impl Parser
                 }
 
                 self.next_token();
-                Ast::Identifier(Identifier
+                Ok(Ast::Identifier(Identifier
                 {
                     name: token.value,
-                })
+                }))
             }
             TOT::DELIMITER if token.value == "(" =>
             {
                 self.parse_grouping()
             }
+            TOT::KEYWORD if token.value == "true" || token.value == "false" =>
+            {
+                self.next_token();
+                Ok(Ast::Bool(Bool
+                {
+                    value: token.value == "true",
+                }))
+            }
             _ =>
-                panic!("Unexpected token while parsing primary expression: {}, next token: {} ", token.value, self.peek(1).unwrap().value),
+            {
+                self.diagnostics.push(Diagnostic::parse(
+                    ParseError::UnexpectedToken(token.value.clone()),
+                    Span::point(token.line, token.column),
+                    format!("Unexpected token '{}' while parsing an expression", token.value),
+                ));
+                self.next_token();
+                Err(())
+            }
         }
     }
 
-    fn parse_function_call(&mut self) -> Ast
+    fn parse_function_call(&mut self) -> PResult<Ast>
     {
-        let name = self.expect(TOT::IDENTIFIER, None).value;
-        self.expect(TOT::DELIMITER, Some("("));
+        let name = self.expect(TOT::IDENTIFIER, None)?.value;
+        self.expect(TOT::DELIMITER, Some("("))?;
 
         let mut args = Vec::new();
 
@@ -364,7 +670,7 @@ impl Parser
         {
             loop
             {
-                args.push(self.parse_expression());
+                args.push(self.parse_expression()?);
 
                 if self.match_token(TOT::DELIMITER, Some(",")).is_none()
                 {
@@ -372,13 +678,13 @@ impl Parser
                 }
             }
 
-            self.expect(TOT::DELIMITER, Some(")"));
+            self.expect(TOT::DELIMITER, Some(")"))?;
         }
 
-        Ast::Call(Call
+        Ok(Ast::Call(Call
         {
             callee: name,
             args,
-        })
+        }))
     }
 }
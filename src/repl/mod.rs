@@ -0,0 +1,142 @@
+use std::io::{self, Write};
+use crate::ast::Ast;
+use crate::codegen::{Instruction, Opcode};
+use crate::compiler::Compiler;
+use crate::diagnostics::report;
+use crate::lexer::{Lexer, TOT};
+use crate::parser::Parser;
+use crate::vm::Vm;
+
+/// An interactive session that lexes, parses, compiles, and executes one
+/// line at a time against a `Vm` that lives for the whole session, so
+/// variables and functions defined earlier stay visible later on. Input that
+/// ends with an unclosed `{` or `(` is held and appended to until it closes,
+/// rather than being reported as an error.
+pub struct Repl
+{
+    vm: Vm,
+}
+
+impl Default for Repl
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Repl
+{
+    pub fn new() -> Self
+    {
+        Repl { vm: Vm::new() }
+    }
+
+    pub fn run(&mut self)
+    {
+        let stdin = io::stdin();
+        let mut buffer = String::new();
+
+        loop
+        {
+            print!("{}", if buffer.is_empty() { "nano> " } else { "...   " });
+            io::stdout().flush().ok();
+
+            let mut line = String::new();
+            if stdin.read_line(&mut line).unwrap_or(0) == 0
+            {
+                println!();
+                break;
+            }
+
+            buffer.push_str(&line);
+
+            if Self::is_incomplete(&buffer)
+            {
+                continue;
+            }
+
+            self.eval(&buffer);
+            buffer.clear();
+        }
+    }
+
+    fn is_incomplete(src: &str) -> bool
+    {
+        let mut lexer = Lexer::new(src.to_string());
+        let tokens = match lexer.tokenize()
+        {
+            Ok(tokens) => tokens,
+            Err(_) => return false,
+        };
+
+        let mut depth = 0i32;
+        for token in &tokens
+        {
+            if token.tot != TOT::DELIMITER
+            {
+                continue;
+            }
+
+            match token.value.as_str()
+            {
+                "{" | "(" => depth += 1,
+                "}" | ")" => depth -= 1,
+                _ => {}
+            }
+        }
+
+        depth > 0
+    }
+
+    fn eval(&mut self, src: &str)
+    {
+        let mut lexer = Lexer::new(src.to_string());
+        let tokens = match lexer.tokenize()
+        {
+            Ok(tokens) => tokens,
+            Err(diagnostics) =>
+            {
+                report(&diagnostics);
+                return;
+            }
+        };
+
+        let mut parser = Parser::new(tokens);
+        let statements = match parser.parse()
+        {
+            Ok(statements) => statements,
+            Err(diagnostics) =>
+            {
+                report(&diagnostics);
+                return;
+            }
+        };
+
+        if statements.is_empty()
+        {
+            return;
+        }
+
+        let is_bare_expr = matches!(
+            statements.last(),
+            Some(Ast::Number(_) | Ast::Str(_) | Ast::Bool(_) | Ast::Identifier(_) | Ast::Binary(_) | Ast::Unary(_) | Ast::Call(_))
+        );
+
+        let mut instructions = Compiler::new().compile(&statements);
+        instructions.pop(); // drop the trailing Halt; the session keeps running across lines
+
+        if is_bare_expr && matches!(instructions.last(), Some(Instruction::Op(Opcode::Pop)))
+        {
+            instructions.pop();
+        }
+
+        if let Some(value) = self.vm.execute(instructions)
+        {
+            if is_bare_expr
+            {
+                println!("{}", value);
+            }
+        }
+    }
+}
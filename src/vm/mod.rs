@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::codegen::{Instruction, Opcode};
+
+#[derive(Debug, Clone)]
+pub enum Value
+{
+    Number(f64),
+    Str(String),
+    Bool(bool),
+}
+
+impl fmt::Display for Value
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+/// A stack-based interpreter for the `Instruction` stream a `Compiler`
+/// produces. Functions live inline in the stream where they were declared,
+/// so a pre-scan records each function's entry point and the index right
+/// after its body, letting execution jump over a `Func` the same way a
+/// `Call` jumps into one. Each `Call` pushes a fresh variable scope onto
+/// `scopes` and `Ret` pops it, so a function's parameters and locals never
+/// collide with the caller's; `Ldv` still walks outward through enclosing
+/// scopes so a function body can see globals defined above it. `Call` also
+/// checks the argument count it carries against the callee's declared arity
+/// (from the pre-scan) and pads or drains the stack to match, so a caller
+/// passing the wrong number of arguments can't throw off the stack for
+/// everything that runs after it. The instruction stream, stack, and scopes
+/// all persist across calls to `execute`, so a REPL can feed it one line at a
+/// time and have earlier variables and functions stay visible.
+pub struct Vm
+{
+    instructions: Vec<Instruction>,
+    stack: Vec<Value>,
+    scopes: Vec<HashMap<String, Value>>,
+    entries: HashMap<String, usize>,
+    skip_to: HashMap<String, usize>,
+    arities: HashMap<String, usize>,
+    frames: Vec<usize>,
+}
+
+impl Default for Vm
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl Vm
+{
+    pub fn new() -> Self
+    {
+        Vm
+        {
+            instructions: Vec::new(),
+            stack: Vec::new(),
+            scopes: vec![HashMap::new()],
+            entries: HashMap::new(),
+            skip_to: HashMap::new(),
+            arities: HashMap::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    fn prescan(instructions: &[Instruction]) -> (HashMap<String, usize>, HashMap<String, usize>, HashMap<String, usize>)
+    {
+        let mut entries = HashMap::new();
+        let mut skip_to = HashMap::new();
+        let mut arities = HashMap::new();
+
+        for (index, instruction) in instructions.iter().enumerate()
+        {
+            match instruction
+            {
+                Instruction::OpWithArg(Opcode::Func, name) =>
+                {
+                    entries.insert(name.clone(), index + 1);
+                }
+                Instruction::Data(marker) =>
+                {
+                    if let Some(name) = marker.strip_prefix("endfunc:")
+                    {
+                        skip_to.insert(name.to_string(), index + 1);
+                    }
+                    else if let Some(rest) = marker.strip_prefix("arity:")
+                    {
+                        if let Some((name, count)) = rest.rsplit_once(':')
+                        {
+                            if let Ok(count) = count.parse()
+                            {
+                                arities.insert(name.to_string(), count);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        (entries, skip_to, arities)
+    }
+
+    fn parse_literal(raw: &str) -> Value
+    {
+        if let Some(rest) = raw.strip_prefix("n:")
+        {
+            Value::Number(rest.parse().unwrap_or(0.0))
+        }
+        else if let Some(rest) = raw.strip_prefix("s:")
+        {
+            Value::Str(rest.to_string())
+        }
+        else if let Some(rest) = raw.strip_prefix("b:")
+        {
+            Value::Bool(rest == "true")
+        }
+        else
+        {
+            Value::Str(raw.to_string())
+        }
+    }
+
+    fn apply_arith(op: &Opcode, left: Value, right: Value) -> Value
+    {
+        match (op, left, right)
+        {
+            (Opcode::Add, Value::Str(a), b) => Value::Str(a + &b.to_string()),
+            (Opcode::Add, a, Value::Str(b)) => Value::Str(a.to_string() + &b),
+            (Opcode::Add, Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+            (Opcode::Sub, Value::Number(a), Value::Number(b)) => Value::Number(a - b),
+            (Opcode::Mul, Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+            (Opcode::Div, Value::Number(a), Value::Number(b)) => Value::Number(a / b),
+            _ => Value::Number(0.0),
+        }
+    }
+
+    fn truthy(value: &Value) -> bool
+    {
+        match value
+        {
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+
+    fn values_equal(left: &Value, right: &Value) -> bool
+    {
+        match (left, right)
+        {
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn compare(op: &Opcode, left: &Value, right: &Value) -> bool
+    {
+        let (left, right) = match (left, right)
+        {
+            (Value::Number(a), Value::Number(b)) => (*a, *b),
+            _ => return false,
+        };
+
+        match op
+        {
+            Opcode::Lt => left < right,
+            Opcode::Lte => left <= right,
+            Opcode::Gt => left > right,
+            Opcode::Gte => left >= right,
+            _ => false,
+        }
+    }
+
+    /// Looks up `name` starting from the innermost (current call's) scope
+    /// and walking outward, so a function body can still read globals
+    /// defined above it. Falls back to `Number(0.0)` for an unbound name, as
+    /// `Ldv` always has.
+    fn resolve(&self, name: &str) -> Value
+    {
+        for scope in self.scopes.iter().rev()
+        {
+            if let Some(value) = scope.get(name)
+            {
+                return value.clone();
+            }
+        }
+        Value::Number(0.0)
+    }
+
+    /// Appends `instructions` to the program and runs only the newly added
+    /// range, so earlier calls' state (the stack, scopes, functions) carries
+    /// over. Returns whatever value, if any, is left on top of the stack once
+    /// execution reaches the end of the stream.
+    pub fn execute(&mut self, instructions: Vec<Instruction>) -> Option<Value>
+    {
+        let start = self.instructions.len();
+        // Jump targets are indices into the batch the compiler produced, so
+        // shift them by where the batch lands in the persistent stream.
+        self.instructions.extend(instructions.into_iter().map(|instruction| match instruction
+        {
+            Instruction::OpWithArg(op @ (Opcode::Jump | Opcode::JumpIfFalse), target) =>
+            {
+                let target = target.parse::<usize>().map_or(target, |t| (t + start).to_string());
+                Instruction::OpWithArg(op, target)
+            }
+            other => other,
+        }));
+
+        let (entries, skip_to, arities) = Self::prescan(&self.instructions);
+        self.entries = entries;
+        self.skip_to = skip_to;
+        self.arities = arities;
+
+        let mut pc = start;
+        while pc < self.instructions.len()
+        {
+            pc = match self.instructions[pc].clone()
+            {
+                Instruction::Data(_) => pc + 1,
+                Instruction::Op(op) => self.exec(op, None, pc),
+                Instruction::OpWithArg(op, arg) => self.exec(op, Some(arg), pc),
+            };
+        }
+
+        self.stack.pop()
+    }
+
+    fn exec(&mut self, op: Opcode, arg: Option<String>, pc: usize) -> usize
+    {
+        match op
+        {
+            Opcode::Push =>
+            {
+                let literal = arg.expect("Push requires a literal operand");
+                self.stack.push(Self::parse_literal(&literal));
+                pc + 1
+            }
+            Opcode::Pop =>
+            {
+                self.stack.pop();
+                pc + 1
+            }
+            Opcode::Ldv =>
+            {
+                let name = arg.expect("Ldv requires a variable name");
+                let value = self.resolve(&name);
+                self.stack.push(value);
+                pc + 1
+            }
+            Opcode::Stv =>
+            {
+                let name = arg.expect("Stv requires a variable name");
+                let value = self.stack.pop().unwrap_or(Value::Number(0.0));
+                self.scopes.last_mut().expect("at least one scope is always active").insert(name, value);
+                pc + 1
+            }
+            Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div =>
+            {
+                let right = self.stack.pop().unwrap_or(Value::Number(0.0));
+                let left = self.stack.pop().unwrap_or(Value::Number(0.0));
+                self.stack.push(Self::apply_arith(&op, left, right));
+                pc + 1
+            }
+            Opcode::Eq =>
+            {
+                let right = self.stack.pop().unwrap_or(Value::Number(0.0));
+                let left = self.stack.pop().unwrap_or(Value::Number(0.0));
+                self.stack.push(Value::Bool(Self::values_equal(&left, &right)));
+                pc + 1
+            }
+            Opcode::Neq =>
+            {
+                let right = self.stack.pop().unwrap_or(Value::Number(0.0));
+                let left = self.stack.pop().unwrap_or(Value::Number(0.0));
+                self.stack.push(Value::Bool(!Self::values_equal(&left, &right)));
+                pc + 1
+            }
+            Opcode::Lt | Opcode::Lte | Opcode::Gt | Opcode::Gte =>
+            {
+                let right = self.stack.pop().unwrap_or(Value::Number(0.0));
+                let left = self.stack.pop().unwrap_or(Value::Number(0.0));
+                self.stack.push(Value::Bool(Self::compare(&op, &left, &right)));
+                pc + 1
+            }
+            Opcode::And =>
+            {
+                let right = self.stack.pop().unwrap_or(Value::Bool(false));
+                let left = self.stack.pop().unwrap_or(Value::Bool(false));
+                self.stack.push(Value::Bool(Self::truthy(&left) && Self::truthy(&right)));
+                pc + 1
+            }
+            Opcode::Or =>
+            {
+                let right = self.stack.pop().unwrap_or(Value::Bool(false));
+                let left = self.stack.pop().unwrap_or(Value::Bool(false));
+                self.stack.push(Value::Bool(Self::truthy(&left) || Self::truthy(&right)));
+                pc + 1
+            }
+            Opcode::Neg =>
+            {
+                let value = self.stack.pop().unwrap_or(Value::Number(0.0));
+                let negated = match value
+                {
+                    Value::Number(n) => Value::Number(-n),
+                    other => other,
+                };
+                self.stack.push(negated);
+                pc + 1
+            }
+            Opcode::Not =>
+            {
+                let value = self.stack.pop().unwrap_or(Value::Bool(false));
+                self.stack.push(Value::Bool(!Self::truthy(&value)));
+                pc + 1
+            }
+            Opcode::Jump =>
+            {
+                let target = arg.expect("Jump requires a target");
+                target.parse().unwrap_or(pc + 1)
+            }
+            Opcode::JumpIfFalse =>
+            {
+                let target = arg.expect("JumpIfFalse requires a target");
+                let value = self.stack.pop().unwrap_or(Value::Bool(false));
+                if Self::truthy(&value)
+                {
+                    pc + 1
+                }
+                else
+                {
+                    target.parse().unwrap_or(pc + 1)
+                }
+            }
+            Opcode::Print =>
+            {
+                let value = self.stack.pop().unwrap_or(Value::Number(0.0));
+                print!("{}", value);
+                pc + 1
+            }
+            Opcode::Println =>
+            {
+                let value = self.stack.pop().unwrap_or(Value::Number(0.0));
+                println!("{}", value);
+                pc + 1
+            }
+            Opcode::Readln =>
+            {
+                let mut line = String::new();
+                std::io::stdin().read_line(&mut line).unwrap_or(0);
+                self.stack.push(Value::Str(line.trim_end().to_string()));
+                pc + 1
+            }
+            Opcode::Func =>
+            {
+                let name = arg.expect("Func requires a name");
+                *self.skip_to.get(&name).unwrap_or(&self.instructions.len())
+            }
+            Opcode::Call =>
+            {
+                let raw = arg.expect("Call requires a callee name");
+                let (name, provided) = match raw.rsplit_once(':')
+                {
+                    Some((name, count)) => (name.to_string(), count.parse().unwrap_or(0)),
+                    None => (raw, 0),
+                };
+
+                // A caller that passes too many or too few arguments would
+                // otherwise leave the operand stack off balance for good,
+                // corrupting whatever statement runs next, so pad or drain it
+                // to the callee's declared arity before jumping in.
+                let expected = self.arities.get(&name).copied().unwrap_or(provided);
+                if provided > expected
+                {
+                    self.stack.truncate(self.stack.len().saturating_sub(provided - expected));
+                }
+                else if provided < expected
+                {
+                    self.stack.extend(std::iter::repeat(Value::Number(0.0)).take(expected - provided));
+                }
+
+                if let Some(&entry) = self.entries.get(&name)
+                {
+                    self.frames.push(pc + 1);
+                    self.scopes.push(HashMap::new());
+                    entry
+                }
+                else
+                {
+                    pc + 1
+                }
+            }
+            Opcode::Ret =>
+            {
+                if self.scopes.len() > 1
+                {
+                    self.scopes.pop();
+                }
+                self.frames.pop().unwrap_or(self.instructions.len())
+            }
+            Opcode::Set => pc + 1,
+            Opcode::Halt => self.instructions.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::compiler::Compiler;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// Lexes, parses, and compiles `src`, then runs it the way the REPL runs
+    /// a line: the trailing `Halt` (and, for a bare expression, the `Pop`
+    /// that follows it) is dropped first so the last expression's value
+    /// survives to be returned instead of being discarded.
+    fn run(src: &str) -> Option<Value>
+    {
+        let tokens = Lexer::new(src.to_string()).tokenize().expect("lex error");
+        let statements = Parser::new(tokens).parse().expect("parse error");
+
+        let mut instructions = Compiler::new().compile(&statements);
+        instructions.pop();
+        if matches!(instructions.last(), Some(Instruction::Op(Opcode::Pop)))
+        {
+            instructions.pop();
+        }
+
+        Vm::new().execute(instructions)
+    }
+
+    #[test]
+    fn arithmetic_follows_precedence()
+    {
+        assert!(matches!(run("2 + 3 * 4"), Some(Value::Number(n)) if n == 14.0));
+    }
+
+    #[test]
+    fn function_call_with_two_arguments()
+    {
+        let result = run("fn add(a, b) { return a + b }\nadd(2, 3)");
+        assert!(matches!(result, Some(Value::Number(n)) if n == 5.0));
+    }
+
+    #[test]
+    fn interpolated_arithmetic_expression()
+    {
+        let result = run("var a = 5\nvar b = 10\n\"{a + b}\"");
+        assert!(matches!(result, Some(Value::Str(s)) if s == "15"));
+    }
+
+    #[test]
+    fn logical_and()
+    {
+        let result = run("var a = true\nvar b = false\na && b");
+        assert!(matches!(result, Some(Value::Bool(b)) if !b));
+    }
+}